@@ -1,51 +1,254 @@
 
 //! Provides the structs and enumerated types that define internal representations of Kiln programs.
-//!   *  Rates are ramp rates in either degrees/hr or AFAP for "As Fast As Possible".
+//!   *  Rates are ramp rates in either degrees/hr, degrees/min or AFAP for "As Fast As Possible".
 //!   *  Steps represent a program step.  They have a target temp, a rate and a hold time.
 //!   *  Programs are just a named, and commented vector of steps.
-//!    * A Run is a program, a date/time and a textual command with an optional image (which is just a binary vector(?))
-//! and image type the purpose of the image is to  show how the run worked out (the finished result of the kiln run).
+//!   *  A Run is a program, a date/time and a textual command with an optional image (which is just a binary vector(?))
+//!      and image type the purpose of the image is to  show how the run worked out (the finished result of the kiln run).
+//!
 //! NOTE: - in tyhe future a run may have a vector of images.
-#![crate_name="programs"]
 pub mod programs {
     use chrono::prelude::*;
+    use std::fmt;
     use std::time::Duration;
     /// How fast the kiln should go from its current temperature to the next one.
+    /// Rates are magnitudes; the direction comes from whether the step's target
+    /// is above or below the current temperature.  So a rate on a step with a
+    /// lower target is a controlled cool, while AFAP down means elements off.
     /// 
+    #[allow(clippy::upper_case_acronyms)]
     #[derive(Copy, Clone, PartialEq, Debug)]
     pub enum RampRate {
         AFAP,
-        DegreesPerHour(f32)
+        DegreesPerHour(f32),
+        DegreesPerMinute(f32)
+    }
+
+    impl RampRate {
+        /// The rate in degrees/hr, or None for AFAP.
+        pub fn per_hour(self) -> Option<f32> {
+            match self {
+                RampRate::AFAP => None,
+                RampRate::DegreesPerHour(r) => Some(r),
+                RampRate::DegreesPerMinute(r) => Some(r * 60.0)
+            }
+        }
+        /// Convert to the DegreesPerHour form.  AFAP stays AFAP.
+        pub fn to_per_hour(self) -> RampRate {
+            match self.per_hour() {
+                Some(r) => RampRate::DegreesPerHour(r),
+                None => RampRate::AFAP
+            }
+        }
+        /// Convert to the DegreesPerMinute form.  AFAP stays AFAP.
+        pub fn to_per_minute(self) -> RampRate {
+            match self.per_hour() {
+                Some(r) => RampRate::DegreesPerMinute(r / 60.0),
+                None => RampRate::AFAP
+            }
+        }
     }
 
     /// A step in a kiln program:
+    /// The optional label is a note like "bubble squeeze" or "anneal".
     ///
-    #[derive(Copy, Clone, PartialEq, Debug)] 
+    #[derive(Clone, PartialEq, Debug)] 
     pub struct Step {
         target : f32,
         ramp_rate : RampRate,
-        hold_time : u32
+        hold_time : u32,
+        label : Option<String>
+    }
+
+    /// Temperature and rate differences smaller than this are treated as equal
+    /// by the semantically_eq methods.
+    pub const SCHEDULE_EPSILON : f32 = 0.01;
+
+    /// Errors reported by the methods of this module.
+    /// Parse line numbers count from 1.
+    ///
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum ProgramError {
+        EmptyProgram,
+        InvalidThickness(f32),
+        InvalidTarget(f32),
+        InvalidRampRate(f32),
+        SubMinuteHold(Duration),
+        HoldTooLong(Duration),
+        Parse { line : usize, reason : String },
+        CountMismatch { line : usize, expected : usize, found : usize }
+    }
+
+    impl fmt::Display for ProgramError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ProgramError::EmptyProgram =>
+                    write!(f, "The program has no steps"),
+                ProgramError::InvalidThickness(t) =>
+                    write!(f, "Invalid glass thickness {}mm", t),
+                ProgramError::InvalidTarget(t) =>
+                    write!(f, "Invalid target temperature {}", t),
+                ProgramError::InvalidRampRate(r) =>
+                    write!(f, "Invalid ramp rate {}", r),
+                ProgramError::SubMinuteHold(d) =>
+                    write!(f, "Hold of {:?} is not a whole number of minutes", d),
+                ProgramError::HoldTooLong(d) =>
+                    write!(f, "Hold of {:?} is too long", d),
+                ProgramError::Parse {line, reason} =>
+                    write!(f, "Line {}: {}", line, reason),
+                ProgramError::CountMismatch {line, expected, found} =>
+                    write!(f, "Line {}: segment count says {} but {} segments were found", line, expected, found)
+            }
+        }
     }
 
+    impl std::error::Error for ProgramError {}
+
     impl Step {
         /// Create a new step object.
         pub fn new(target: f32, ramp : RampRate, hold: u32) -> Step {
             Step {
-                target : target, ramp_rate: ramp, hold_time: hold
+                target, ramp_rate: ramp, hold_time: hold, label: None
             }
         }
+        /// Create a step with the hold given as a Duration.  Holds are stored
+        /// in whole minutes so anything finer is an error.
+        #[allow(clippy::manual_is_multiple_of)] // is_multiple_of needs Rust 1.87.
+        pub fn with_hold(target: f32, ramp : RampRate, hold : Duration) -> Result<Step, ProgramError> {
+            if hold.subsec_nanos() != 0 || hold.as_secs() % 60 != 0 {
+                return Err(ProgramError::SubMinuteHold(hold));
+            }
+            let minutes = u32::try_from(hold.as_secs() / 60)
+                .map_err(|_| ProgramError::HoldTooLong(hold))?;
+            Ok(Step::new(target, ramp, minutes))
+        }
         /// Selector - return the target tempaerature of a step.
-        pub fn target_temp(self) -> f32 {
+        pub fn target_temp(&self) -> f32 {
             self.target
         }
         /// selector - return the ramp rate for a step.
-        pub fn ramp_rate(self) -> RampRate {
+        pub fn ramp_rate(&self) -> RampRate {
             self.ramp_rate
         }
         /// Selector - return the hold time for a step.
-        pub fn hold_time(self) -> u32 {
+        pub fn hold_time(&self) -> u32 {
             self.hold_time
         }
+        /// Selector - return the step's label if it has one.
+        pub fn label(&self) -> Option<String> {
+            self.label.clone()
+        }
+        /// Set the label of a step.
+        pub fn set_label(&mut self, label : &str) -> &Step {
+            self.label = Some(String::from(label));
+            self
+        }
+        /// Remove the label from a step.
+        pub fn clear_label(&mut self) -> &Step {
+            self.label = None;
+            self
+        }
+        /// Compare two steps allowing for float rounding in the target and rate.
+        /// Rates are compared in degrees/hr regardless of the unit they were given in.
+        /// Holds are compared exactly; a hold omitted by some source is 0 here.
+        /// Labels are ignored.
+        pub fn semantically_eq(&self, other : &Step) -> bool {
+            let ramps_eq = match (self.ramp_rate.per_hour(), other.ramp_rate.per_hour()) {
+                (None, None) => true,
+                (Some(a), Some(b)) => (a - b).abs() < SCHEDULE_EPSILON,
+                _ => false
+            };
+            ramps_eq && (self.target - other.target).abs() < SCHEDULE_EPSILON
+                && self.hold_time == other.hold_time
+        }
+        /// Selector - return the hold time as a Duration.
+        pub fn hold_duration(&self) -> Duration {
+            Duration::from_secs(u64::from(self.hold_time) * 60)
+        }
+        /// Estimate how long the step takes (ramp plus hold) starting at from.
+        /// A rate is followed in either direction, so a rate on a lower target is
+        /// a controlled cool.  AFAP, up or down, is assumed to move at afap_rate
+        /// degrees/hr.  A step that is already at its target has no ramp time.
+        ///
+        pub fn estimated_duration(&self, from : f32, afap_rate : f32) -> Result<Duration, ProgramError> {
+            let minutes = self.ramp_minutes(from, afap_rate)? + self.hold_time as f64;
+            Duration::try_from_secs_f64(minutes * 60.0)
+                .map_err(|_| ProgramError::InvalidRampRate(self.ramp_rate.per_hour().unwrap_or(afap_rate)))
+        }
+        // Minutes spent ramping from from to the target.
+        fn ramp_minutes(&self, from : f32, afap_rate : f32) -> Result<f64, ProgramError> {
+            for t in [from, self.target] {
+                if !t.is_finite() {
+                    return Err(ProgramError::InvalidTarget(t));
+                }
+            }
+            let distance = (self.target - from).abs();
+            if distance == 0.0 {
+                return Ok(0.0);
+            }
+            let rate = self.ramp_rate.per_hour().unwrap_or(afap_rate);
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(ProgramError::InvalidRampRate(rate));
+            }
+            Ok(distance as f64 / rate as f64 * 60.0)
+        }
+    }
+
+    /// Text layouts understood by physical kiln controllers.
+    /// Each dialect describes how segments (rate, temp, hold) are written
+    /// and how AFAP is expressed.
+    ///
+    #[derive(Copy, Clone, PartialEq, Debug)]
+    pub enum ControllerDialect {
+        /// Orton/Bartlett style (Skutt KMT, Vary-Fire): rate in degrees/hr
+        /// with 9999 meaning full power, hold as HH.MM.
+        Orton
+    }
+
+    impl ControllerDialect {
+        /// The rate value this dialect uses for AFAP.
+        pub fn afap_rate(self) -> u32 {
+            match self {
+                ControllerDialect::Orton => 9999
+            }
+        }
+        /// The longest hold, in minutes, this dialect can express.
+        pub fn max_hold(self) -> u32 {
+            match self {
+                ControllerDialect::Orton => 99 * 60 + 59
+            }
+        }
+    }
+
+    /// Programs whose last target is above this (°F) leave the kiln too hot
+    /// to open; see Program::warnings.
+    pub const SAFE_OPEN_TEMP : f32 = 150.0;
+
+    /// Something about a program that looks suspicious but is not
+    /// necessarily wrong.  See Program::warnings.  step is a 0-based index
+    /// while the message numbers steps from 1 as a user would.
+    ///
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct ProgramWarning {
+        step : usize,
+        message : String
+    }
+
+    impl ProgramWarning {
+        /// Create a warning about the step at index step.
+        pub fn new(step : usize, message : &str) -> ProgramWarning {
+            ProgramWarning {
+                step, message : String::from(message)
+            }
+        }
+        /// Selector - index of the step the warning is about.
+        pub fn step(&self) -> usize {
+            self.step
+        }
+        /// Selector - what looks wrong and what to do about it.
+        pub fn message(&self) -> String {
+            self.message.clone()
+        }
     }
 
     /// A fully described kiln program:
@@ -66,13 +269,31 @@ pub mod programs {
             }
         }
         /// Create a program with an initial set of steps.
-        pub fn from_steps(name : &str, description : &str, program: &Vec<Step>) -> Program {
+        pub fn from_steps(name : &str, description : &str, program: &[Step]) -> Program {
             Program {
                 name : String::from(name),
                 description : String::from(description),
-                program : program.clone()
+                program : program.to_vec()
             }
         }
+        /// Create a program from any iterator of steps.
+        pub fn from_iter_named<I : IntoIterator<Item = Step>>(name : &str, description : &str, steps : I) -> Program {
+            Program {
+                name : String::from(name),
+                description : String::from(description),
+                program : steps.into_iter().collect()
+            }
+        }
+        /// Change the name of a program.
+        pub fn set_name(&mut self, name : &str) -> &Program {
+            self.name = String::from(name);
+            self
+        }
+        /// Change the description of a program.
+        pub fn set_description(&mut self, description : &str) -> &Program {
+            self.description = String::from(description);
+            self
+        }
         /// Append a new step to a program.
         pub fn add_step(&mut self, step : Step) -> &Program {
             self.program.push(step);
@@ -85,6 +306,13 @@ pub mod programs {
 
             self
         }
+        /// Append an anneal soak and controlled cool sized for glass of the
+        /// given thickness (see annealing::anneal_steps).  The program is left
+        /// alone if the thickness is invalid.
+        pub fn with_anneal(&mut self, thickness_mm : f32) -> Result<&Program, ProgramError> {
+            self.program.extend(super::annealing::anneal_steps(thickness_mm)?);
+            Ok(self)
+        }
         /// Clear the steps from a program making it empty.
         pub fn clear(&mut self) -> &Program {
             self.program.clear();
@@ -94,6 +322,22 @@ pub mod programs {
         pub fn steps(&self) -> Vec<Step> {
             self.program.clone()
         }
+        /// Number of steps in the program.
+        pub fn len(&self) -> usize {
+            self.program.len()
+        }
+        /// True if the program has no steps.
+        pub fn is_empty(&self) -> bool {
+            self.program.is_empty()
+        }
+        /// Selector - return the step at index, or None if out of range.
+        pub fn step(&self, index : usize) -> Option<&Step> {
+            self.program.get(index)
+        }
+        /// Iterate over the steps without cloning them.
+        pub fn iter(&self) -> std::slice::Iter<'_, Step> {
+            self.program.iter()
+        }
         /// Selector - return the name of a program.
         pub fn name(&self) -> String {
             self.name.clone()
@@ -102,6 +346,296 @@ pub mod programs {
         pub fn description(&self) -> String {
             self.description.clone()
         }
+        /// Compare the schedules of two programs with Step::semantically_eq.
+        /// Names and descriptions are ignored.
+        pub fn semantically_eq(&self, other : &Program) -> bool {
+            self.program.len() == other.program.len()
+                && self.program.iter().zip(other.program.iter()).all(|(a, b)| a.semantically_eq(b))
+        }
+        /// True if any step ramps AFAP.  Useful before exporting to a
+        /// controller that has no AFAP setting.
+        pub fn has_afap(&self) -> bool {
+            self.program.iter().any(|s| s.ramp_rate == RampRate::AFAP)
+        }
+        /// Indices of the AFAP steps.
+        pub fn afap_step_indices(&self) -> Vec<usize> {
+            self.program.iter().enumerate()
+                .filter(|(_, s)| s.ramp_rate == RampRate::AFAP)
+                .map(|(i, _)| i)
+                .collect()
+        }
+        /// Check for problems that make a program unusable: no steps, a non-finite
+        /// or negative target, or a ramp rate that is not finite and positive.  Unlike
+        /// warnings, a program that fails this should not be saved or run.
+        ///
+        pub fn validate_structure(&self) -> Result<(), ProgramError> {
+            if self.program.is_empty() {
+                return Err(ProgramError::EmptyProgram);
+            }
+            for step in &self.program {
+                if !step.target.is_finite() || step.target < 0.0 {
+                    return Err(ProgramError::InvalidTarget(step.target));
+                }
+                if let Some(rate) = step.ramp_rate.per_hour() {
+                    if !rate.is_finite() || rate <= 0.0 {
+                        return Err(ProgramError::InvalidRampRate(rate));
+                    }
+                }
+            }
+            Ok(())
+        }
+        /// Look for things in the program that are probably mistakes.
+        /// These are heuristics so an empty result does not mean the program
+        /// is good and a warning does not mean it is bad.
+        ///
+        pub fn warnings(&self) -> Vec<ProgramWarning> {
+            self.warnings_with(SAFE_OPEN_TEMP)
+        }
+        /// Same as warnings but with a different safe-to-open temperature.
+        pub fn warnings_with(&self, safe_open_temp : f32) -> Vec<ProgramWarning> {
+            let mut result = vec![];
+
+            // A step that drops below the previous target with no hold and then
+            // rises past it again looks like two targets got transposed.
+            // Dips with a hold are taken to be intentional soaks.
+            for i in 1..self.program.len().saturating_sub(1) {
+                let prior = &self.program[i - 1];
+                let step = &self.program[i];
+                let next = &self.program[i + 1];
+                if step.hold_time == 0 && step.target < prior.target && prior.target < next.target {
+                    result.push(ProgramWarning::new(i, &format!(
+                        "Step {} drops to {} between {} and {} - should it be swapped with step {}?",
+                        i + 1, step.target, prior.target, next.target, i
+                    )));
+                }
+            }
+
+            // Ending hot means someone may open the kiln too soon.
+            if let Some(last) = self.program.last() {
+                if last.target > safe_open_temp {
+                    result.push(ProgramWarning::new(self.program.len() - 1, &format!(
+                        "Program ends at {} - add a cool down to {} or below before opening the kiln",
+                        last.target, safe_open_temp
+                    )));
+                }
+            }
+            result
+        }
+        /// A hash of the schedule that stays the same between runs and Rust
+        /// versions (64 bit FNV-1a).  The name, description and labels are
+        /// ignored.  Rates are hashed in degrees/hr so the unit a rate was given
+        /// in does not matter.  Step order does matter.
+        ///
+        pub fn content_hash(&self) -> u64 {
+            const FNV_OFFSET : u64 = 0xcbf29ce484222325;
+            const FNV_PRIME : u64 = 0x100000001b3;
+
+            let mut hash = FNV_OFFSET;
+            let mut add = |bytes : &[u8]| {
+                for b in bytes {
+                    hash ^= u64::from(*b);
+                    hash = hash.wrapping_mul(FNV_PRIME);
+                }
+            };
+            for step in &self.program {
+                add(&(step.target + 0.0).to_le_bytes());       // + 0.0 folds -0 into 0.
+                match step.ramp_rate.per_hour() {
+                    None => add(&[0]),
+                    Some(r) => {
+                        add(&[1]);
+                        add(&(r + 0.0).to_le_bytes());
+                    }
+                }
+                add(&step.hold_time.to_le_bytes());
+            }
+            hash
+        }
+        /// Mean of the ramp rates in degrees/hr, ignoring AFAP steps.
+        /// None if no step has a rate.
+        pub fn average_ramp_rate(&self) -> Option<f32> {
+            let rates : Vec<f32> = self.program.iter().filter_map(|s| s.ramp_rate.per_hour()).collect();
+            if rates.is_empty() {
+                None
+            } else {
+                Some(rates.iter().sum::<f32>() / rates.len() as f32)
+            }
+        }
+        /// Estimate the temperature profile of a firing as (minutes, temperature)
+        /// points, starting at ambient.  There is a point at the end of each ramp
+        /// and at the end of each hold.  Ramp times come from
+        /// Step::estimated_duration, so a step already at its target takes no
+        /// time, and a rate (or afap_rate when an AFAP step has to move) that is
+        /// not positive is an InvalidRampRate error.
+        ///
+        pub fn simulate(&self, ambient : f32, afap_rate : f32) -> Result<Vec<(f32, f32)>, ProgramError> {
+            let mut minutes : f64 = 0.0;
+            let mut temp = ambient;
+            let mut result = vec![(0.0, temp)];
+
+            for step in &self.program {
+                // A tiny but positive rate can take longer than an f32 can count.
+                let too_long = ProgramError::InvalidRampRate(step.ramp_rate.per_hour().unwrap_or(afap_rate));
+                minutes += step.ramp_minutes(temp, afap_rate)?;
+                temp = step.target;
+                if !(minutes as f32).is_finite() {
+                    return Err(too_long);
+                }
+                result.push((minutes as f32, temp));
+                if step.hold_time > 0 {
+                    minutes += step.hold_time as f64;
+                    if !(minutes as f32).is_finite() {
+                        return Err(too_long);
+                    }
+                    result.push((minutes as f32, temp));
+                }
+            }
+            Ok(result)
+        }
+        /// Produce a gnuplot data file of the simulated firing: comment header
+        /// then minutes and temperature columns.  Plot with
+        /// `plot 'file' with lines`.
+        ///
+        pub fn to_gnuplot_data(&self, ambient : f32, afap_rate : f32) -> Result<String, ProgramError> {
+            let points = self.simulate(ambient, afap_rate)?;
+
+            // Every header line is commented, even in multi-line descriptions.
+            let mut result = String::new();
+            for line in format!("{}: {}", self.name, self.description).lines() {
+                result.push_str(&format!("# {}\n", line));
+            }
+            result.push_str("# minutes temperature\n");
+            for (minutes, temp) in points {
+                result.push_str(&format!("{} {}\n", minutes, temp));
+            }
+            Ok(result)
+        }
+        /// Format the program as a controller segment list.  The first line
+        /// is the segment count, then one line per segment:
+        /// segment-number rate temperature hold.
+        /// Rates are rounded and clamped to 1..afap_rate-1.  Non-finite
+        /// targets or rates and holds longer than the dialect's max_hold
+        /// are errors.
+        ///
+        pub fn to_controller_format(&self, dialect : ControllerDialect) -> Result<String, ProgramError> {
+            let mut result = format!("{}\n", self.program.len());
+            for (i, step) in self.program.iter().enumerate() {
+                if !step.target.is_finite() {
+                    return Err(ProgramError::InvalidTarget(step.target));
+                }
+                if step.hold_time > dialect.max_hold() {
+                    return Err(ProgramError::HoldTooLong(step.hold_duration()));
+                }
+                // Rates are whole numbers below the AFAP value: rounding must
+                // not turn a slow ramp into 0 or a fast one into AFAP.
+                let rate = match step.ramp_rate.per_hour() {
+                    None => dialect.afap_rate().to_string(),
+                    Some(r) if !r.is_finite() => return Err(ProgramError::InvalidRampRate(r)),
+                    Some(r) => format!("{:.0}", r.round().clamp(1.0, (dialect.afap_rate() - 1) as f32))
+                };
+                let hold = match dialect {
+                    ControllerDialect::Orton =>
+                        format!("{:02}.{:02}", step.hold_time / 60, step.hold_time % 60)
+                };
+                result.push_str(&format!("{} {} {:.0} {}\n", i + 1, rate, step.target, hold));
+            }
+            Ok(result)
+        }
+        /// Parse a controller segment list (as produced by to_controller_format)
+        /// back into a program.  Blank lines and extra whitespace are ignored
+        /// and the leading segment count line is optional.  Segments must be
+        /// numbered 1, 2, ... in order.
+        ///
+        pub fn from_controller_format(
+            name : &str, description : &str, text : &str, dialect : ControllerDialect
+        ) -> Result<Program, ProgramError> {
+            let mut result = Program::new(name, description);
+            let mut count : Option<(usize, usize)> = None;      // (line, count)
+
+            for (i, line) in text.lines().enumerate() {
+                let line_no = i + 1;
+                let bad = |reason : String| ProgramError::Parse {line: line_no, reason};
+                let fields : Vec<&str> = line.split_whitespace().collect();
+                match fields.len() {
+                    0 => continue,
+                    1 if count.is_none() && result.program.is_empty() => {
+                        count = Some((line_no, fields[0].parse().map_err(|_| {
+                            bad(format!("Invalid segment count '{}'", fields[0]))
+                        })?));
+                    },
+                    4 => {
+                        let segment = result.program.len() + 1;
+                        if fields[0].parse::<usize>() != Ok(segment) {
+                            return Err(bad(format!("Expected segment {} but found '{}'", segment, fields[0])));
+                        }
+                        let rate : f32 = fields[1].parse().map_err(|_| {
+                            bad(format!("Invalid rate '{}'", fields[1]))
+                        })?;
+                        let target : f32 = fields[2].parse().map_err(|_| {
+                            bad(format!("Invalid temperature '{}'", fields[2]))
+                        })?;
+                        let hold = parse_hold(fields[3], dialect).ok_or_else(|| {
+                            bad(format!("Invalid hold '{}'", fields[3]))
+                        })?;
+                        if !rate.is_finite() || rate <= 0.0 || rate > dialect.afap_rate() as f32 {
+                            return Err(ProgramError::InvalidRampRate(rate));
+                        }
+                        if !target.is_finite() || target < 0.0 {
+                            return Err(ProgramError::InvalidTarget(target));
+                        }
+                        let ramp = if rate == dialect.afap_rate() as f32 {
+                            RampRate::AFAP
+                        } else {
+                            RampRate::DegreesPerHour(rate)
+                        };
+                        result.add_step(Step::new(target, ramp, hold));
+                    },
+                    _ => return Err(bad(String::from("Expected: segment rate temperature hold")))
+                }
+            }
+            if let Some((line, expected)) = count {
+                if expected != result.program.len() {
+                    return Err(ProgramError::CountMismatch {line, expected, found: result.program.len()});
+                }
+            }
+            Ok(result)
+        }
+    }
+
+    /// Allows `steps.into_iter().collect::<Program>()`.  The program has an
+    /// empty name and description; use set_name/set_description afterwards.
+    impl FromIterator<Step> for Program {
+        fn from_iter<I : IntoIterator<Item = Step>>(steps : I) -> Program {
+            Program::from_iter_named("", "", steps)
+        }
+    }
+
+    /// Allows `for step in &program`.
+    impl<'a> IntoIterator for &'a Program {
+        type Item = &'a Step;
+        type IntoIter = std::slice::Iter<'a, Step>;
+        fn into_iter(self) -> Self::IntoIter {
+            self.program.iter()
+        }
+    }
+
+    // Turn a controller hold string into minutes.  Minutes are always two
+    // digits so "00.5" is an error rather than 5 minutes.
+    fn parse_hold(hold : &str, dialect : ControllerDialect) -> Option<u32> {
+        match dialect {
+            ControllerDialect::Orton => {
+                let (hours, minutes) = hold.split_once('.')?;
+                if minutes.len() != 2 || !minutes.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                let hours : u32 = hours.parse().ok()?;
+                let minutes : u32 = minutes.parse().ok()?;
+                if minutes < 60 {
+                    hours.checked_mul(60)?.checked_add(minutes)
+                } else {
+                    None
+                }
+            }
+        }
     }
 
     /// A project is a description, a time/date that it was run
@@ -180,6 +714,30 @@ pub mod programs {
     }
 
 
+    #[cfg(test)]
+    mod ramp_tests {
+        use super::*;
+
+        #[test]
+        fn per_hour_0() {
+            assert_eq!(RampRate::DegreesPerMinute(5.0).per_hour(), Some(300.0));
+            assert_eq!(RampRate::DegreesPerHour(300.0).per_hour(), Some(300.0));
+            assert_eq!(RampRate::AFAP.per_hour(), None);
+        }
+        #[test]
+        fn convert_0() {
+            // 5 degrees/min is 300 degrees/hr and back.
+            assert_eq!(RampRate::DegreesPerMinute(5.0).to_per_hour(), RampRate::DegreesPerHour(300.0));
+            assert_eq!(RampRate::DegreesPerHour(300.0).to_per_minute(), RampRate::DegreesPerMinute(5.0));
+        }
+        #[test]
+        fn convert_1() {
+            // AFAP is unit-less.
+            assert_eq!(RampRate::AFAP.to_per_hour(), RampRate::AFAP);
+            assert_eq!(RampRate::AFAP.to_per_minute(), RampRate::AFAP);
+        }
+    }
+
     #[cfg(test)]
     mod step_tests {
         use super::*;
@@ -188,7 +746,7 @@ pub mod programs {
         fn new_0() {
             let r = Step::new(1000.0, RampRate::DegreesPerHour(100.0), 32);
             assert_eq!(
-                r, Step {target: 1000.0, ramp_rate: RampRate::DegreesPerHour(100.0), hold_time: 32}
+                r, Step {target: 1000.0, ramp_rate: RampRate::DegreesPerHour(100.0), hold_time: 32, label: None}
             );
         }
         #[test]
@@ -212,6 +770,101 @@ pub mod programs {
             let r = Step::new(1000.0, RampRate::DegreesPerHour(100.0), 32);
             assert_eq!(r.hold_time(), 32);
         }
+        #[test]
+        fn label_0() {
+            // Steps start unlabeled.
+            let r = Step::new(1000.0, RampRate::DegreesPerHour(100.0), 32);
+            assert_eq!(r.label(), None);
+        }
+        #[test]
+        fn label_1() {
+            let mut r = Step::new(1225.0, RampRate::DegreesPerHour(100.0), 30);
+            r.set_label("bubble squeeze");
+            assert_eq!(r.label(), Some(String::from("bubble squeeze")));
+            r.clear_label();
+            assert_eq!(r.label(), None);
+        }
+        #[test]
+        fn semantic_eq_0() {
+            let a = Step::new(1000.0, RampRate::DegreesPerHour(300.0), 0);
+            assert!(a.semantically_eq(&Step::new(1000.001, RampRate::DegreesPerHour(299.999), 0)));
+            assert!(!a.semantically_eq(&Step::new(1001.0, RampRate::DegreesPerHour(300.0), 0)));
+            assert!(!a.semantically_eq(&Step::new(1000.0, RampRate::AFAP, 0)));
+            assert!(!a.semantically_eq(&Step::new(1000.0, RampRate::DegreesPerHour(300.0), 10)));
+            assert!(a.semantically_eq(&Step::new(1000.0, RampRate::DegreesPerMinute(5.0), 0)));
+        }
+        #[test]
+        fn hold_2() {
+            // 90 minute hold.
+            let r = Step::with_hold(1000.0, RampRate::AFAP, Duration::from_secs(90 * 60)).unwrap();
+            assert_eq!(r.hold_time(), 90);
+            assert_eq!(r.hold_duration(), Duration::from_secs(90 * 60));
+        }
+        #[test]
+        fn hold_3() {
+            // 6 hour anneal.
+            let r = Step::with_hold(900.0, RampRate::DegreesPerHour(50.0), Duration::from_secs(6 * 3600)).unwrap();
+            assert_eq!(r.hold_time(), 360);
+            assert_eq!(r.hold_duration(), Duration::from_secs(6 * 3600));
+        }
+        #[test]
+        fn hold_4() {
+            // Sub-minute precision is rejected.
+            let d = Duration::from_secs(90);
+            assert_eq!(
+                Step::with_hold(1000.0, RampRate::AFAP, d),
+                Err(ProgramError::SubMinuteHold(d))
+            );
+            let d = Duration::from_millis(60_500);
+            assert_eq!(
+                Step::with_hold(1000.0, RampRate::AFAP, d),
+                Err(ProgramError::SubMinuteHold(d))
+            );
+        }
+        #[test]
+        fn hold_5() {
+            // More minutes than fit in a u32.
+            let d = Duration::from_secs((u64::from(u32::MAX) + 1) * 60);
+            assert_eq!(
+                Step::with_hold(1000.0, RampRate::AFAP, d),
+                Err(ProgramError::HoldTooLong(d))
+            );
+        }
+        #[test]
+        fn duration_0() {
+            // Heating at a rate plus the hold.
+            let r = Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30);
+            assert_eq!(r.estimated_duration(100.0, 600.0), Ok(Duration::from_secs(210 * 60)));
+        }
+        #[test]
+        fn duration_1() {
+            // Controlled cool: 1000 -> 700 at 150/hr is two hours, a slope of -150/hr.
+            let r = Step::new(700.0, RampRate::DegreesPerHour(150.0), 0);
+            let d = r.estimated_duration(1000.0, 600.0).unwrap();
+            assert_eq!(d, Duration::from_secs(2 * 3600));
+            assert_eq!((700.0 - 1000.0) / (d.as_secs_f32() / 3600.0), -150.0);
+        }
+        #[test]
+        fn duration_2() {
+            // AFAP cooling goes at afap_rate instead, then holds.
+            let r = Step::new(700.0, RampRate::AFAP, 60);
+            assert_eq!(r.estimated_duration(1000.0, 600.0), Ok(Duration::from_secs(90 * 60)));
+        }
+        #[test]
+        fn duration_3() {
+            // Already at target: just the hold, whatever the rate.
+            let r = Step::new(900.0, RampRate::DegreesPerHour(0.0), 30);
+            assert_eq!(r.estimated_duration(900.0, 600.0), Ok(Duration::from_secs(30 * 60)));
+        }
+        #[test]
+        fn duration_4() {
+            // Rates that never get there are errors.
+            let r = Step::new(900.0, RampRate::DegreesPerHour(0.0), 30);
+            assert_eq!(r.estimated_duration(100.0, 600.0), Err(ProgramError::InvalidRampRate(0.0)));
+            let r = Step::new(900.0, RampRate::AFAP, 30);
+            assert_eq!(r.estimated_duration(100.0, -1.0), Err(ProgramError::InvalidRampRate(-1.0)));
+            assert_eq!(r.estimated_duration(f32::INFINITY, 600.0), Err(ProgramError::InvalidTarget(f32::INFINITY)));
+        }
     }
 
     #[cfg(test)]
@@ -249,6 +902,36 @@ pub mod programs {
             );
         }
         #[test]
+        fn set_0() {
+            let mut pgm = Program::new("small-full", "Full fuse for small pieces");
+            pgm.set_name("full-fuse");
+            pgm.set_description("Full fuse");
+            assert_eq!(pgm, Program::new("full-fuse", "Full fuse"));
+        }
+        #[test]
+        fn collect_0() {
+            // Collect the rate steps into a program.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm : Program = steps.into_iter().filter(|s| s.ramp_rate() != RampRate::AFAP).collect();
+            assert_eq!(pgm.len(), 3);
+            assert_eq!(pgm.name(), "");
+            assert_eq!(pgm.description(), "");
+        }
+        #[test]
+        fn collect_1() {
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_iter_named("testing", "description", steps.iter().cloned());
+            assert_eq!(pgm, Program::from_steps("testing", "description", &steps));
+        }
+        #[test]
         fn add_1() {
             // Add  a step to an empty project:
 
@@ -380,10 +1063,542 @@ pub mod programs {
             let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
             assert_eq!(pgm.steps(), steps);
         }
+        #[test]
+        fn iter_0() {
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            assert_eq!(pgm.iter().map(|s| s.hold_time).sum::<u32>(), 90);
+
+            let mut total = 0;
+            for step in &pgm {
+                total += step.hold_time;
+            }
+            assert_eq!(total, 90);
+        }
+        #[test]
+        fn len_0() {
+            let pgm = Program::new("small-full", "Full fuse for small pieces");
+            assert_eq!(pgm.len(), 0);
+            assert!(pgm.is_empty());
+        }
+        #[test]
+        fn len_1() {
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            assert_eq!(pgm.len(), 4);
+            assert!(!pgm.is_empty());
+        }
+        #[test]
+        fn step_0() {
+            // In range.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            assert_eq!(pgm.step(0), Some(&steps[0]));
+            assert_eq!(pgm.step(3), Some(&steps[3]));
+        }
+        #[test]
+        fn step_1() {
+            // Out of range.
+            let pgm = Program::from_steps(
+                "testing", "description", &[Step::new(1000.0, RampRate::AFAP, 30)]
+            );
+            assert_eq!(pgm.step(1), None);
+            assert_eq!(Program::new("empty", "no steps").step(0), None);
+        }
+        #[test]
+        fn afap_0() {
+            // No AFAP steps.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1450.0, RampRate::DegreesPerMinute(5.0), 15)
+            ];
+            let pgm = Program::from_steps("testing", "description", &steps);
+            assert!(!pgm.has_afap());
+            assert_eq!(pgm.afap_step_indices(), Vec::<usize>::new());
+        }
+        #[test]
+        fn afap_1() {
+            // Mixed.
+            let steps = vec![
+                Step::new(1000.0, RampRate::AFAP, 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            assert!(pgm.has_afap());
+            assert_eq!(pgm.afap_step_indices(), vec![0, 3]);
+        }
+        #[test]
+        fn anneal_0() {
+            let mut pgm = Program::from_steps("testing", "description", &[
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 10)
+            ]);
+            pgm.with_anneal(3.0).unwrap();
+            assert_eq!(pgm.len(), 6);
+            assert_eq!(pgm.step(2).unwrap().target_temp(), 900.0);
+            assert_eq!(pgm.step(2).unwrap().hold_time(), 60);
+            assert_eq!(pgm.step(5).unwrap().target_temp(), 70.0);
+
+            // Bad thickness leaves the program as it was.
+            assert!(pgm.with_anneal(f32::NAN).is_err());
+            assert_eq!(pgm.len(), 6);
+        }
+        #[test]
+        fn validate_0() {
+            // Empty programs are invalid.
+            assert_eq!(Program::new("empty", "no steps").validate_structure(), Err(ProgramError::EmptyProgram));
+        }
+        #[test]
+        fn validate_1() {
+            // Bad targets.
+            let pgm = Program::from_steps("t", "d", &[
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(f32::NAN, RampRate::AFAP, 30)
+            ]);
+            assert!(matches!(pgm.validate_structure(), Err(ProgramError::InvalidTarget(t)) if t.is_nan()));
+            let pgm = Program::from_steps("t", "d", &[Step::new(-10.0, RampRate::AFAP, 30)]);
+            assert_eq!(pgm.validate_structure(), Err(ProgramError::InvalidTarget(-10.0)));
+            let pgm = Program::from_steps("t", "d", &[Step::new(f32::INFINITY, RampRate::AFAP, 30)]);
+            assert_eq!(pgm.validate_structure(), Err(ProgramError::InvalidTarget(f32::INFINITY)));
+        }
+        #[test]
+        fn validate_2() {
+            // Bad ramp rates.
+            let pgm = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::DegreesPerHour(-150.0), 30)]);
+            assert_eq!(pgm.validate_structure(), Err(ProgramError::InvalidRampRate(-150.0)));
+            let pgm = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::DegreesPerMinute(0.0), 30)]);
+            assert_eq!(pgm.validate_structure(), Err(ProgramError::InvalidRampRate(0.0)));
+            // f32::MAX degrees/minute is infinite degrees/hr.
+            let pgm = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::DegreesPerMinute(f32::MAX), 30)]);
+            assert_eq!(pgm.validate_structure(), Err(ProgramError::InvalidRampRate(f32::INFINITY)));
+        }
+        #[test]
+        fn validate_3() {
+            // A good program.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            assert_eq!(pgm.validate_structure(), Ok(()));
+        }
+        #[test]
+        fn warnings_0() {
+            // A sensible program has no warnings.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30),
+                Step::new(100.0, RampRate::AFAP, 0)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            assert_eq!(pgm.warnings(), vec![]);
+        }
+        #[test]
+        fn warnings_1() {
+            // Transposed targets.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1450.0, RampRate::DegreesPerHour(300.0), 0),
+                Step::new(1200.0, RampRate::DegreesPerHour(500.0), 0),
+                Step::new(1460.0, RampRate::DegreesPerHour(500.0), 10),
+                Step::new(900.0, RampRate::AFAP, 30),
+                Step::new(100.0, RampRate::AFAP, 0)
+            ];
+            let pgm = Program::from_steps("testing", "description", &steps);
+            let warnings = pgm.warnings();
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].step(), 2);
+            assert!(warnings[0].message().contains("swapped with step 2"));
+        }
+        #[test]
+        fn warnings_2() {
+            // A dip and hold (bubble squeeze) before going to full fuse is fine.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 0),
+                Step::new(1200.0, RampRate::AFAP, 30),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 10),
+                Step::new(900.0, RampRate::AFAP, 30),
+                Step::new(100.0, RampRate::AFAP, 0)
+            ];
+            let pgm = Program::from_steps("testing", "description", &steps);
+            assert_eq!(pgm.warnings(), vec![]);
+        }
+        #[test]
+        fn warnings_3() {
+            // Ending hot is flagged.
+            let steps = vec![
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 10),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("testing", "description", &steps);
+            let warnings = pgm.warnings();
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].step(), 1);
+            assert!(warnings[0].message().contains("ends at 900"));
+
+            // Unless the threshold says that's ok.
+            assert_eq!(pgm.warnings_with(1000.0), vec![]);
+        }
+        #[test]
+        fn warnings_4() {
+            // Ending near ambient is fine.
+            let steps = vec![
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 10),
+                Step::new(900.0, RampRate::AFAP, 30),
+                Step::new(100.0, RampRate::DegreesPerHour(300.0), 0)
+            ];
+            let pgm = Program::from_steps("testing", "description", &steps);
+            assert_eq!(pgm.warnings(), vec![]);
+        }
+        #[test]
+        fn hash_0() {
+            // Same schedule, same hash regardless of name, labels and rate units.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let a = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            let mut labeled = steps.clone();
+            labeled[0] = Step::new(1000.0, RampRate::DegreesPerMinute(5.0), 30);
+            labeled[2].set_label("anneal");
+            let b = Program::from_steps("other", "something else", &labeled);
+            assert_eq!(a.content_hash(), b.content_hash());
+        }
+        #[test]
+        fn hash_1() {
+            // Reordering steps changes the hash.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let swapped = vec![steps[1].clone(), steps[0].clone(), steps[2].clone()];
+            let a = Program::from_steps("testing", "description", &steps);
+            let b = Program::from_steps("testing", "description", &swapped);
+            assert_ne!(a.content_hash(), b.content_hash());
+        }
+        #[test]
+        fn hash_2() {
+            // Stable value for an empty program and AFAP differs from a rate.
+            assert_eq!(Program::new("a", "b").content_hash(), 0xcbf29ce484222325);
+            let afap = Program::from_steps("t", "d", &[Step::new(900.0, RampRate::AFAP, 0)]);
+            let rate = Program::from_steps("t", "d", &[Step::new(900.0, RampRate::DegreesPerHour(0.0), 0)]);
+            assert_ne!(afap.content_hash(), rate.content_hash());
+        }
+        #[test]
+        fn average_rate_0() {
+            // All AFAP - no average.
+            let steps = vec![
+                Step::new(1000.0, RampRate::AFAP, 30),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("testing", "description", &steps);
+            assert_eq!(pgm.average_ramp_rate(), None);
+            assert_eq!(Program::new("empty", "no steps").average_ramp_rate(), None);
+        }
+        #[test]
+        fn average_rate_1() {
+            // Mixed - AFAP is ignored.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(600.0), 15),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            assert_eq!(pgm.average_ramp_rate(), Some(400.0));
+
+            // Per minute rates count in degrees/hr.
+            let pgm = Program::from_steps("testing", "description", &[
+                Step::new(1000.0, RampRate::DegreesPerMinute(5.0), 30),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 15)
+            ]);
+            assert_eq!(pgm.average_ramp_rate(), Some(400.0));
+        }
+        #[test]
+        fn semantic_eq_0() {
+            // Programs that differ only by float rounding.
+            let a = Program::from_steps("a", "first", &[
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 0),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ]);
+            let b = Program::from_steps("b", "second", &[
+                Step::new(999.999, RampRate::DegreesPerHour(300.0001), 30),
+                Step::new(1450.0, RampRate::DegreesPerHour(499.9999), 0),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ]);
+            assert_ne!(a, b);
+            assert!(a.semantically_eq(&b));
+        }
+        #[test]
+        fn semantic_eq_1() {
+            // Different step counts are not equal.
+            let a = Program::from_steps("a", "first", &[
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ]);
+            let b = Program::from_steps("a", "first", &[
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30)
+            ]);
+            assert!(!a.semantically_eq(&b));
+            assert!(!b.semantically_eq(&a));
+        }
+        #[test]
+        fn simulate_0() {
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1450.0, RampRate::AFAP, 0),
+                Step::new(900.0, RampRate::DegreesPerHour(275.0), 60)
+            ];
+            let pgm = Program::from_steps("testing", "description", &steps);
+            assert_eq!(
+                pgm.simulate(100.0, 900.0),
+                Ok(vec![
+                    (0.0, 100.0),
+                    (180.0, 1000.0), (210.0, 1000.0),
+                    (240.0, 1450.0),
+                    (360.0, 900.0), (420.0, 900.0)
+                ])
+            );
+        }
+        #[test]
+        fn simulate_1() {
+            // Controlled cooling goes at its rate, AFAP cooling at afap_rate.
+            let controlled = Program::from_steps("t", "d", &[Step::new(700.0, RampRate::DegreesPerHour(150.0), 0)]);
+            let afap = Program::from_steps("t", "d", &[Step::new(700.0, RampRate::AFAP, 0)]);
+            assert_eq!(controlled.simulate(1000.0, 600.0).unwrap()[1], (120.0, 700.0));
+            assert_eq!(afap.simulate(1000.0, 600.0).unwrap()[1], (30.0, 700.0));
+        }
+        #[test]
+        fn simulate_2() {
+            // Zero rates are errors rather than NaN or infinite minutes...
+            let zero = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::DegreesPerHour(0.0), 0)]);
+            assert_eq!(zero.simulate(70.0, 600.0), Err(ProgramError::InvalidRampRate(0.0)));
+            let afap = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::AFAP, 0)]);
+            assert_eq!(afap.simulate(70.0, 0.0), Err(ProgramError::InvalidRampRate(0.0)));
+            assert_eq!(afap.to_gnuplot_data(70.0, 0.0), Err(ProgramError::InvalidRampRate(0.0)));
+
+            // ...unless the step doesn't have to move.
+            let hold = Program::from_steps("t", "d", &[Step::new(70.0, RampRate::DegreesPerHour(0.0), 30)]);
+            assert_eq!(hold.simulate(70.0, 600.0), Ok(vec![(0.0, 70.0), (0.0, 70.0), (30.0, 70.0)]));
+        }
+        #[test]
+        fn simulate_3() {
+            // A subnormal rate would take longer than an f32 can hold.
+            let slow = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::DegreesPerHour(1e-40), 0)]);
+            assert_eq!(slow.simulate(70.0, 600.0), Err(ProgramError::InvalidRampRate(1e-40)));
+            assert_eq!(slow.to_gnuplot_data(70.0, 600.0), Err(ProgramError::InvalidRampRate(1e-40)));
+        }
+        #[test]
+        fn gnuplot_0() {
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("testing", "description", &steps);
+            let text = pgm.to_gnuplot_data(70.0, 600.0).unwrap();
+            let data : Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+            assert!(text.starts_with("# testing: description\n"));
+            assert_eq!(data[0], "0 70");
+            assert_eq!(data.len(), 5);
+        }
+        #[test]
+        fn gnuplot_1() {
+            // Multi-line descriptions stay in the comment header.
+            let pgm = Program::from_steps(
+                "testing", "line one\nline two\n", &[Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30)]
+            );
+            let text = pgm.to_gnuplot_data(70.0, 600.0).unwrap();
+            assert!(text.starts_with("# testing: line one\n# line two\n# minutes temperature\n"));
+            let data : Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+            assert_eq!(data, vec!["0 70", "186 1000", "216 1000"]);
+        }
+        #[test]
+        fn controller_0() {
+            // Orton export: count line + one line per segment, AFAP is 9999.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 90),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            let text = pgm.to_controller_format(ControllerDialect::Orton).unwrap();
+            let lines : Vec<&str> = text.lines().collect();
+            assert_eq!(lines.len(), 5);
+            assert_eq!(lines[0], "4");
+            assert_eq!(lines[3], "3 500 1450 01.30");
+            assert_eq!(lines[4], "4 9999 900 00.30");
+
+            // Controllers want degrees/hr:
+            let pgm = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::DegreesPerMinute(5.0), 0)]);
+            assert_eq!(pgm.to_controller_format(ControllerDialect::Orton).unwrap(), "1\n1 300 1000 00.00\n");
+        }
+        #[test]
+        fn controller_4() {
+            // Rates at the edges stay rates and still re-import.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(0.4), 0),
+                Step::new(1450.0, RampRate::DegreesPerHour(9999.4), 0),
+                Step::new(1460.0, RampRate::DegreesPerHour(20000.0), 0)
+            ];
+            let pgm = Program::from_steps("t", "d", &steps);
+            let text = pgm.to_controller_format(ControllerDialect::Orton).unwrap();
+            assert_eq!(text, "3\n1 1 1000 00.00\n2 9998 1450 00.00\n3 9998 1460 00.00\n");
+            let back = Program::from_controller_format("t", "d", &text, ControllerDialect::Orton).unwrap();
+            assert!(!back.has_afap());
+            assert_eq!(back.step(0).unwrap().ramp_rate(), RampRate::DegreesPerHour(1.0));
+        }
+        #[test]
+        fn controller_6() {
+            // Values the controller can't hold are export errors.
+            let pgm = Program::from_steps("t", "d", &[Step::new(f32::NAN, RampRate::DegreesPerHour(f32::NAN), 0)]);
+            assert!(matches!(
+                pgm.to_controller_format(ControllerDialect::Orton),
+                Err(ProgramError::InvalidTarget(t)) if t.is_nan()
+            ));
+            let pgm = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::DegreesPerMinute(f32::MAX), 0)]);
+            assert_eq!(
+                pgm.to_controller_format(ControllerDialect::Orton),
+                Err(ProgramError::InvalidRampRate(f32::INFINITY))
+            );
+            let pgm = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::AFAP, 99 * 60 + 59)]);
+            assert_eq!(pgm.to_controller_format(ControllerDialect::Orton), Ok(String::from("1\n1 9999 1000 99.59\n")));
+            let pgm = Program::from_steps("t", "d", &[Step::new(1000.0, RampRate::AFAP, 100 * 60)]);
+            assert_eq!(
+                pgm.to_controller_format(ControllerDialect::Orton),
+                Err(ProgramError::HoldTooLong(Duration::from_secs(100 * 3600)))
+            );
+        }
+        #[test]
+        fn controller_1() {
+            // Round trip through the Orton format.
+            let steps = vec![
+                Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                Step::new(1250.0, RampRate::DegreesPerHour(300.0), 15),
+                Step::new(1450.0, RampRate::DegreesPerHour(500.0), 90),
+                Step::new(900.0, RampRate::AFAP, 30)
+            ];
+            let pgm = Program::from_steps("small-full", "Full fuse for small pieces", &steps);
+            let text = pgm.to_controller_format(ControllerDialect::Orton).unwrap();
+            assert_eq!(
+                Program::from_controller_format(
+                    "small-full", "Full fuse for small pieces", &text, ControllerDialect::Orton
+                ),
+                Ok(pgm)
+            );
+        }
+        #[test]
+        fn controller_2() {
+            // Whitespace is tolerated and the count is optional:
+            let text = "\n   1    300  1000   00.30\n\n\t2 9999 900 01.00  \n";
+            let pgm = Program::from_controller_format("t", "d", text, ControllerDialect::Orton).unwrap();
+            assert_eq!(
+                pgm.steps(),
+                vec![
+                    Step::new(1000.0, RampRate::DegreesPerHour(300.0), 30),
+                    Step::new(900.0, RampRate::AFAP, 60)
+                ]
+            );
+        }
+        #[test]
+        fn controller_3() {
+            // Errors:
+            assert_eq!(
+                Program::from_controller_format("t", "d", "2\n1 300 1000 00.30\n", ControllerDialect::Orton),
+                Err(ProgramError::CountMismatch {line: 1, expected: 2, found: 1})
+            );
+            // The count's line is reported even after blank lines.
+            assert_eq!(
+                Program::from_controller_format("t", "d", "\n\n3\n1 300 1000 00.30\n", ControllerDialect::Orton),
+                Err(ProgramError::CountMismatch {line: 3, expected: 3, found: 1})
+            );
+            assert!(matches!(
+                Program::from_controller_format("t", "d", "\n1 300 1000 00.75\n", ControllerDialect::Orton),
+                Err(ProgramError::Parse {line: 2, ..})
+            ));
+            assert!(matches!(
+                Program::from_controller_format("t", "d", "1 300 1000 00.5\n", ControllerDialect::Orton),
+                Err(ProgramError::Parse {line: 1, ..})
+            ));
+            assert_eq!(
+                Program::from_controller_format("t", "d", "1 -300 1000 00.30\n", ControllerDialect::Orton),
+                Err(ProgramError::InvalidRampRate(-300.0))
+            );
+            assert_eq!(
+                Program::from_controller_format("t", "d", "1 300 -10 00.30\n", ControllerDialect::Orton),
+                Err(ProgramError::InvalidTarget(-10.0))
+            );
+        }
+        #[test]
+        fn controller_5() {
+            // Hostile numbers are errors, not panics or infinite steps.
+            assert!(matches!(
+                Program::from_controller_format("t", "d", "1 300 1000 99999999.30\n", ControllerDialect::Orton),
+                Err(ProgramError::Parse {line: 1, ..})
+            ));
+            assert_eq!(
+                Program::from_controller_format("t", "d", "1 inf 1000 00.30\n", ControllerDialect::Orton),
+                Err(ProgramError::InvalidRampRate(f32::INFINITY))
+            );
+            assert_eq!(
+                Program::from_controller_format("t", "d", "1 300 inf 00.30\n", ControllerDialect::Orton),
+                Err(ProgramError::InvalidTarget(f32::INFINITY))
+            );
+            assert!(matches!(
+                Program::from_controller_format("t", "d", "1 NaN 1000 00.30\n", ControllerDialect::Orton),
+                Err(ProgramError::InvalidRampRate(r)) if r.is_nan()
+            ));
+            assert_eq!(
+                Program::from_controller_format("t", "d", "1 1e30 1000 00.30\n", ControllerDialect::Orton),
+                Err(ProgramError::InvalidRampRate(1e30))
+            );
+        }
+        #[test]
+        fn controller_7() {
+            // Segment numbers must count up from 1.
+            assert!(matches!(
+                Program::from_controller_format(
+                    "t", "d", "xyz 300 1000 00.30\n7 300 1100 00.30\n", ControllerDialect::Orton
+                ),
+                Err(ProgramError::Parse {line: 1, ..})
+            ));
+            assert!(matches!(
+                Program::from_controller_format(
+                    "t", "d", "2\n1 300 1000 00.30\n3 300 1100 00.30\n", ControllerDialect::Orton
+                ),
+                Err(ProgramError::Parse {line: 3, ..})
+            ));
+        }
     }
     #[cfg(test)] 
     mod project_test {
         use super::*;
+        use std::time::Duration;
         #[test]
         fn new_1() {
             // Construct now
@@ -500,3 +1715,136 @@ pub mod programs {
     
 }
 
+/// Annealing schedules sized by glass thickness.  Values follow the standard
+/// Bullseye chart for fully fused pieces: anneal at 900 °F, then cool in
+/// three stages with rates that slow down as the glass gets thicker.
+pub mod annealing {
+    use super::programs::{ProgramError, RampRate, Step};
+    use std::time::Duration;
+
+    /// Temperature the anneal soak is held at.
+    pub const ANNEAL_TEMP : f32 = 900.0;
+    /// Temperatures the cooling stages end at.
+    pub const COOL_TARGETS : [f32; 3] = [800.0, 700.0, 70.0];
+    /// Thicknesses above this are rejected.  That is far past the chart, and
+    /// extrapolating further gives cooling rates too slow to mean anything.
+    pub const MAX_THICKNESS_MM : f32 = 1000.0;
+
+    // (max thickness mm, soak minutes, cooling rates for each stage)
+    const SCHEDULE : [(f32, u32, [f32; 3]); 6] = [
+        (6.0, 60, [150.0, 270.0, 900.0]),
+        (12.0, 120, [80.0, 144.0, 480.0]),
+        (19.0, 180, [36.0, 65.0, 216.0]),
+        (25.0, 240, [20.0, 36.0, 120.0]),
+        (38.0, 360, [9.0, 16.0, 54.0]),
+        (50.0, 480, [5.0, 9.0, 30.0])
+    ];
+
+    // Soak minutes and cooling rates for a thickness.  Past the end of the
+    // chart the soak grows by an hour per 6.35mm (1/4") and the rates slow
+    // with the square of the thickness.
+    fn schedule_for(thickness_mm : f32) -> Result<(u32, [f32; 3]), ProgramError> {
+        if !thickness_mm.is_finite() || thickness_mm <= 0.0 || thickness_mm > MAX_THICKNESS_MM {
+            return Err(ProgramError::InvalidThickness(thickness_mm));
+        }
+        for (max, soak, rates) in SCHEDULE {
+            if thickness_mm <= max {
+                return Ok((soak, rates));
+            }
+        }
+        let (max, _, rates) = SCHEDULE[SCHEDULE.len() - 1];
+        let scale = (max / thickness_mm).powi(2);
+        Ok((
+            ((thickness_mm / 6.35).ceil() as u32).saturating_mul(60),
+            rates.map(|r| r * scale)
+        ))
+    }
+
+    /// How long to hold at the anneal temperature for glass of a thickness.
+    /// The thickness must be positive and at most MAX_THICKNESS_MM.
+    /// The anneal temperature is accepted so other charts can be added; the
+    /// only chart so far is for ANNEAL_TEMP and any other temperature
+    /// (including NaN) is an InvalidTarget error.
+    pub fn recommended_soak(thickness_mm : f32, anneal_temp : f32) -> Result<Duration, ProgramError> {
+        if anneal_temp != ANNEAL_TEMP {
+            return Err(ProgramError::InvalidTarget(anneal_temp));
+        }
+        let (soak, _) = schedule_for(thickness_mm)?;
+        Ok(Duration::from_secs(u64::from(soak) * 60))
+    }
+
+    /// The anneal soak followed by the controlled cool for a thickness.
+    pub fn anneal_steps(thickness_mm : f32) -> Result<Vec<Step>, ProgramError> {
+        let (soak, rates) = schedule_for(thickness_mm)?;
+        let mut soak_step = Step::new(ANNEAL_TEMP, RampRate::AFAP, soak);
+        soak_step.set_label("anneal");
+
+        let mut result = vec![soak_step];
+        for (target, rate) in COOL_TARGETS.iter().zip(rates.iter()) {
+            result.push(Step::new(*target, RampRate::DegreesPerHour(*rate), 0));
+        }
+        Ok(result)
+    }
+
+    #[cfg(test)]
+    mod annealing_tests {
+        use super::*;
+
+        #[test]
+        fn soak_0() {
+            // Thin pieces.
+            assert_eq!(recommended_soak(3.0, ANNEAL_TEMP), Ok(Duration::from_secs(60 * 60)));
+            assert_eq!(recommended_soak(6.0, ANNEAL_TEMP), Ok(Duration::from_secs(60 * 60)));
+        }
+        #[test]
+        fn soak_1() {
+            // Thick pieces.
+            assert_eq!(recommended_soak(12.0, ANNEAL_TEMP), Ok(Duration::from_secs(2 * 60 * 60)));
+            assert_eq!(recommended_soak(25.0, ANNEAL_TEMP), Ok(Duration::from_secs(4 * 60 * 60)));
+        }
+        #[test]
+        fn soak_2() {
+            // Off the chart is longer than the chart.
+            assert_eq!(recommended_soak(64.0, ANNEAL_TEMP), Ok(Duration::from_secs(11 * 60 * 60)));
+        }
+        #[test]
+        fn steps_0() {
+            let steps = anneal_steps(12.0).unwrap();
+            assert_eq!(
+                steps.iter().map(|s| s.target_temp()).collect::<Vec<f32>>(),
+                vec![900.0, 800.0, 700.0, 70.0]
+            );
+            assert_eq!(steps[0].hold_time(), 120);
+            assert_eq!(steps[0].label(), Some(String::from("anneal")));
+            assert_eq!(steps[1].ramp_rate(), RampRate::DegreesPerHour(80.0));
+            assert_eq!(steps[3].ramp_rate(), RampRate::DegreesPerHour(480.0));
+        }
+        #[test]
+        fn invalid_0() {
+            // Thicknesses that are not finite, not positive or absurdly large.
+            for t in [f32::INFINITY, 0.0, -3.0, 1e12] {
+                assert_eq!(recommended_soak(t, ANNEAL_TEMP), Err(ProgramError::InvalidThickness(t)));
+                assert_eq!(anneal_steps(t), Err(ProgramError::InvalidThickness(t)));
+            }
+        }
+        #[test]
+        fn invalid_1() {
+            // NaN doesn't quietly produce a 0 minute soak.
+            assert!(matches!(recommended_soak(f32::NAN, ANNEAL_TEMP), Err(ProgramError::InvalidThickness(t)) if t.is_nan()));
+            assert!(matches!(anneal_steps(f32::NAN), Err(ProgramError::InvalidThickness(t)) if t.is_nan()));
+        }
+        #[test]
+        fn invalid_2() {
+            // There's no chart for other anneal temperatures.
+            assert_eq!(recommended_soak(12.0, 950.0), Err(ProgramError::InvalidTarget(950.0)));
+            assert!(matches!(recommended_soak(12.0, f32::NAN), Err(ProgramError::InvalidTarget(t)) if t.is_nan()));
+        }
+        #[test]
+        fn limit_0() {
+            // The thickest allowed piece still has a usable schedule.
+            let steps = anneal_steps(MAX_THICKNESS_MM).unwrap();
+            assert_eq!(steps[0].hold_time(), 158 * 60);
+            assert!(steps[1..].iter().all(|s| s.ramp_rate().per_hour().unwrap() > 0.0));
+        }
+    }
+}
@@ -1,8 +1,5 @@
 
 
-mod lib;
-use lib::programs;
-
 fn main() {
     
     println!("Hello, world!");